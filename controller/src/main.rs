@@ -5,7 +5,7 @@
 use anyhow::Context;
 use cache::EntryCache;
 use clap::{Parser, Args, Subcommand};
-use cs2::{CS2Handle, Module, CS2Offsets, EntitySystem, CS2Model, Globals, BuildInfo};
+use cs2::{CS2Handle, Module, CS2Offsets, EntitySystem, CS2Model, Globals, BuildInfo, ReadProfileNode};
 use cs2_schema_generated::{definition::SchemaScope, RuntimeOffsetProvider, RuntimeOffset};
 use enhancements::Enhancement;
 use imgui::{Condition, Ui};
@@ -20,7 +20,7 @@ use std::{
     path::PathBuf,
     rc::Rc,
     sync::Arc,
-    time::{Duration, Instant},
+    time::Instant,
 };
 use view::ViewController;
 use windows::Win32::System::Console::GetConsoleProcessList;
@@ -36,8 +36,14 @@ mod settings;
 mod settings_ui;
 mod cache;
 mod enhancements;
+mod schema_export;
+mod supervisor;
 mod winver;
 
+use crate::schema_export::SchemaFormat;
+
+use crate::supervisor::{HealthStatus, SupervisedEnhancement};
+
 pub trait UpdateInputState {
     fn is_key_down(&self, key: imgui::Key) -> bool;
     fn is_key_pressed(&self, key: imgui::Key, repeating: bool) -> bool;
@@ -82,8 +88,9 @@ pub struct Application {
     pub class_name_cache: EntryCache<u64, Option<String>>,
     pub view_controller: ViewController,
 
-    pub enhancements: Vec<Rc<RefCell<dyn Enhancement>>>,
+    pub enhancements: Vec<SupervisedEnhancement>,
 
+    pub frame_read_profile: ReadProfileNode,
     pub frame_read_calls: usize,
     pub last_total_read_calls: usize,
 
@@ -102,6 +109,66 @@ impl Application {
         self.settings.borrow_mut()
     }
 
+    /// Health of each enhancement (name, status, last error) for display in the
+    /// settings UI.
+    pub fn enhancement_health(&self) -> Vec<(&'static str, HealthStatus, Option<String>)> {
+        let now = Instant::now();
+        self.enhancements
+            .iter()
+            .map(|enhancement| {
+                (
+                    enhancement.name,
+                    enhancement.status(now),
+                    enhancement.last_error().map(str::to_string),
+                )
+            })
+            .collect()
+    }
+
+    /// Clear the health state of an enhancement, re-enabling a quarantined
+    /// feature on user request.
+    pub fn reset_enhancement(&mut self, name: &str) {
+        for enhancement in self.enhancements.iter_mut() {
+            if enhancement.name == name {
+                enhancement.reset();
+            }
+        }
+    }
+
+    /// Render the supervision status of every enhancement and let the user
+    /// manually reset a backing-off or quarantined feature.
+    fn render_enhancement_health(&mut self, ui: &imgui::Ui) {
+        let health = self.enhancement_health();
+        let mut reset = None;
+
+        ui.window(obfstr!("Enhancement Health"))
+            .always_auto_resize(true)
+            .build(|| {
+                for (name, status, last_error) in health.iter() {
+                    let status = match status {
+                        HealthStatus::Healthy => "healthy".to_string(),
+                        HealthStatus::BackingOff { retry_in } => {
+                            format!("backing off ({:.1}s)", retry_in.as_secs_f32())
+                        }
+                        HealthStatus::Quarantined => "quarantined".to_string(),
+                    };
+                    ui.text(format!("{}: {}", name, status));
+
+                    if let Some(error) = last_error {
+                        ui.text_colored([1.0, 0.4, 0.4, 1.0], error);
+                        ui.same_line();
+                        if ui.small_button(&format!("Reset##{}", name)) {
+                            reset = Some(*name);
+                        }
+                    }
+                }
+            });
+
+        if let Some(name) = reset {
+            self.reset_enhancement(name);
+        }
+    }
+
     pub fn pre_update(&mut self, context: &mut imgui::Context) -> anyhow::Result<()> {
         if self.settings_dirty {
             self.settings_dirty = false;
@@ -122,13 +189,17 @@ impl Application {
         {
             let mut settings = self.settings.borrow_mut();
             for enhancement in self.enhancements.iter() {
-                let mut hack = enhancement.borrow_mut();
+                let mut hack = enhancement.instance.borrow_mut();
                 if hack.update_settings(ui, &mut *settings)? {
                     self.settings_dirty = true;
                 }
             }
         }
 
+        if self.settings_visible {
+            self.render_enhancement_health(ui);
+        }
+
         let settings = self.settings.borrow();
         if ui.is_key_pressed_no_repeat(settings.key_settings.0) {
             log::debug!("Toogle settings");
@@ -149,6 +220,7 @@ impl Application {
             .reference_schema::<Globals>(&[self.cs2_offsets.globals, 0])?
             .cached()
             .with_context(|| obfstr!("failed to read globals").to_string())?;
+        cs2::record_bytes(std::mem::size_of::<Globals>());
 
         let update_context = UpdateContext {
             cs2: &self.cs2,
@@ -163,11 +235,34 @@ impl Application {
             model_cache: &self.model_cache,
         };
 
-        for enhancement in self.enhancements.iter() {
-            let mut hack = enhancement.borrow_mut();
-            hack.update(&update_context)?;
+        let now = Instant::now();
+
+        /* Healthy enhancements, and those whose backoff or quarantine retry time
+         * has passed, update this frame, sequentially and in deterministic order. */
+        for enhancement in self.enhancements.iter_mut() {
+            if !enhancement.ready(now) {
+                continue;
+            }
+
+            cs2::push_span(enhancement.name);
+            let reads_before = self.cs2.ke_interface.total_read_calls();
+            let result = enhancement.instance.borrow_mut().update(&update_context);
+            /* Attribute every read the enhancement issued (through any path) to
+             * its span via the driver's global read-call counter. */
+            cs2::record_reads(self.cs2.ke_interface.total_read_calls() - reads_before);
+            cs2::pop_span();
+
+            match result {
+                Ok(()) => enhancement.record_success(),
+                Err(err) => {
+                    log::warn!("Enhancement {} failed: {:#}", enhancement.name, err);
+                    enhancement.record_failure(now, &err);
+                }
+            }
         }
 
+        self.frame_read_profile = cs2::take_frame_profile();
+
         let read_calls = self.cs2.ke_interface.total_read_calls();
         self.frame_read_calls = read_calls - self.last_total_read_calls;
         self.last_total_read_calls = read_calls;
@@ -219,12 +314,70 @@ impl Application {
             ]);
             ui.text(text)
         }
+        self.render_read_profile(ui);
 
         for hack in self.enhancements.iter() {
-            let hack = hack.borrow();
+            let hack = hack.instance.borrow();
             hack.render(&*settings, ui, &self.view_controller);
         }
     }
+
+    /// Render the per-enhancement read attribution collected during
+    /// `update` below the read counter in the top right corner.
+    fn render_read_profile(&self, ui: &imgui::Ui) {
+        let mut y = 52.0;
+        for node in self.frame_read_profile.children.iter() {
+            y = self.render_read_profile_node(ui, node, 0, y);
+        }
+    }
+
+    fn render_read_profile_node(
+        &self,
+        ui: &imgui::Ui,
+        node: &ReadProfileNode,
+        depth: usize,
+        y: f32,
+    ) -> f32 {
+        let reads = node.total_reads();
+        if reads == 0 {
+            return y;
+        }
+
+        let text = format!(
+            "{:indent$}{}: {} reads / {}",
+            "",
+            node.name,
+            reads,
+            format_bytes(node.total_bytes()),
+            indent = depth * 2,
+        );
+        ui.set_cursor_pos([
+            ui.window_size()[0] - ui.calc_text_size(&text)[0] - 10.0,
+            y,
+        ]);
+        ui.text(text);
+
+        let mut y = y + 14.0;
+        for child in node.children.iter() {
+            y = self.render_read_profile_node(ui, child, depth + 1, y);
+        }
+        y
+    }
+}
+
+/// Format a byte count for the read profile HUD, e.g. `9.1 KB`.
+fn format_bytes(bytes: usize) -> String {
+    const KB: f64 = 1024.0;
+    const MB: f64 = KB * 1024.0;
+
+    let bytes = bytes as f64;
+    if bytes >= MB {
+        format!("{:.1} MB", bytes / MB)
+    } else if bytes >= KB {
+        format!("{:.1} KB", bytes / KB)
+    } else {
+        format!("{} B", bytes as usize)
+    }
 }
 
 fn show_critical_error(message: &str) {
@@ -287,6 +440,10 @@ enum AppCommand {
 #[derive(Debug, Args)]
 struct SchemaDumpArgs {
     pub target_file: PathBuf,
+
+    /// Output format for the dumped schema
+    #[clap(long, value_enum, default_value_t = SchemaFormat::Json)]
+    pub format: SchemaFormat,
 }
 
 fn is_console_invoked() -> bool {
@@ -311,7 +468,17 @@ fn main_schema_dump(args: &SchemaDumpArgs) -> anyhow::Result<()> {
         .open(&args.target_file)?;
 
     let mut output = BufWriter::new(output);
-    serde_json::to_writer_pretty(&mut output, &schema)?;
+    match args.format {
+        SchemaFormat::Json => serde_json::to_writer_pretty(&mut output, &schema)?,
+        SchemaFormat::Cpp => std::io::Write::write_all(
+            &mut output,
+            schema_export::generate_cpp(&schema).as_bytes(),
+        )?,
+        SchemaFormat::Rust => std::io::Write::write_all(
+            &mut output,
+            schema_export::generate_rust(&schema).as_bytes(),
+        )?,
+    }
     log::info!("Schema dumped to {}", args.target_file.to_string_lossy());
     Ok(())
 }
@@ -369,10 +536,14 @@ fn main_overlay() -> anyhow::Result<()> {
         cs2_build_info.build_datetime
     );
 
-    let cs2_offsets = Arc::new(
-        CS2Offsets::resolve_offsets(&cs2)
-            .with_context(|| obfstr!("failed to load CS2 offsets").to_string())?,
+    let (offsets, compatibility) = CS2Offsets::resolve_for_build(&cs2, &cs2_build_info)
+        .with_context(|| obfstr!("failed to load CS2 offsets").to_string())?;
+    log::info!(
+        "{} ({:?})",
+        obfstr!("Resolved CS2 offsets"),
+        compatibility
     );
+    let cs2_offsets = Arc::new(offsets);
 
     setup_runtime_offset_provider(&cs2)?;
 
@@ -389,6 +560,7 @@ fn main_overlay() -> anyhow::Result<()> {
             let cs2 = cs2.clone();
             move |model| {
                 let model_name = cs2.read_string(&[*model as u64 + 0x08, 0], Some(32))?;
+                cs2::record_bytes(model_name.len());
                 log::debug!(
                     "{} {}. Caching.",
                     obfstr!("Discovered new player model"),
@@ -404,9 +576,11 @@ fn main_overlay() -> anyhow::Result<()> {
                 let fn_get_class_schema = cs2.reference_schema::<u64>(&[
                     *vtable + 0x00, // First entry in V-Table is GetClassSchema
                 ])?;
+                cs2::record_bytes(std::mem::size_of::<u64>());
 
                 let mut asm_buffer = [0u8; 0x10];
                 cs2.read_slice(&[fn_get_class_schema], &mut asm_buffer)?;
+                cs2::record_bytes(asm_buffer.len());
 
                 // lea rcx, <class schema>
                 if asm_buffer[9] != 0x48 || asm_buffer[10] != 0x8D || asm_buffer[11] != 0x15 {
@@ -429,6 +603,7 @@ fn main_overlay() -> anyhow::Result<()> {
                 }
 
                 let class_name = cs2.read_string(&[class_schema + 0x08, 0], Some(32))?;
+                cs2::record_bytes(class_name.len());
                 log::trace!("Resolved vtable class name {:X} to {}", vtable, class_name);
                 Ok(Some(class_name))
             }
@@ -436,16 +611,25 @@ fn main_overlay() -> anyhow::Result<()> {
         view_controller: ViewController::new(cs2_offsets.clone()),
 
         enhancements: vec![
-            Rc::new(RefCell::new(PlayerESP::new())),
-            Rc::new(RefCell::new(BombInfo::new())),
-            Rc::new(RefCell::new(TriggerBot::new(LocalCrosshair::new(
-                cs2_offsets.offset_crosshair_id,
-            )))),
-            Rc::new(RefCell::new(AntiAimPunsh::new())),
+            SupervisedEnhancement::new("PlayerESP", Rc::new(RefCell::new(PlayerESP::new()))),
+            SupervisedEnhancement::new("BombInfo", Rc::new(RefCell::new(BombInfo::new()))),
+            SupervisedEnhancement::new(
+                "TriggerBot",
+                Rc::new(RefCell::new(TriggerBot::new(LocalCrosshair::new(
+                    cs2_offsets.offset_crosshair_id,
+                )))),
+            ),
+            SupervisedEnhancement::new("AntiAimPunsh", Rc::new(RefCell::new(AntiAimPunsh::new()))),
         ],
 
-        last_total_read_calls: 0,
+        frame_read_profile: ReadProfileNode {
+            name: "frame".to_string(),
+            reads: 0,
+            bytes: 0,
+            children: Vec::new(),
+        },
         frame_read_calls: 0,
+        last_total_read_calls: 0,
 
         settings: settings.clone(),
         settings_visible: false,
@@ -462,8 +646,6 @@ fn main_overlay() -> anyhow::Result<()> {
     }
 
     log::info!("{}", obfstr!("App initialized. Spawning overlay."));
-    let mut update_fail_count = 0;
-    let mut update_timeout: Option<(Instant, Duration)> = None;
     overlay.main_loop(
         {
             let app = app.clone();
@@ -480,26 +662,11 @@ fn main_overlay() -> anyhow::Result<()> {
         move |ui| {
             let mut app = app.borrow_mut();
 
-            if let Some((timeout, target)) = &update_timeout {
-                if timeout.elapsed() > *target {
-                    update_timeout = None;
-                } else {
-                    /* Not updating. On timeout... */
-                    return true;
-                }
-            }
-
+            /* Failures of an enhancement's `update` are handled by the supervision
+             * layer in `Application::update`; errors of `update_settings` and of
+             * the shared per-frame setup surface here. */
             if let Err(err) = app.update(ui) {
-                if update_fail_count >= 10 {
-                    log::error!("Over 10 errors occurred. Waiting 1s and try again.");
-                    log::error!("Last error: {:#}", err);
-
-                    update_timeout = Some((Instant::now(), Duration::from_millis(1000)));
-                    update_fail_count = 0;
-                    return true;
-                } else {
-                    update_fail_count += 1;
-                }
+                log::warn!("Frame update failed: {:#}", err);
             }
 
             app.render(ui);
@@ -0,0 +1,308 @@
+use std::{collections::HashSet, fmt::Write};
+
+use cs2_schema_generated::definition::SchemaScope;
+
+/// Output format for the schema dump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SchemaFormat {
+    /// Pretty printed JSON of the resolved schema tree.
+    Json,
+    /// A C++ header with `namespace`/`struct` declarations and `constexpr`
+    /// member offsets.
+    Cpp,
+    /// A Rust module tree of offset constants.
+    Rust,
+}
+
+/// Flattened view of the resolved schema the code generators render. Lowering
+/// the `SchemaScope` tree into this owned form keeps the generators (and their
+/// tests) independent of the external schema types.
+struct Module {
+    name: String,
+    classes: Vec<Class>,
+}
+
+struct Class {
+    name: String,
+    members: Vec<Member>,
+}
+
+struct Member {
+    name: String,
+    offset: u64,
+}
+
+/// Lower the walked schema into the generator's intermediate representation.
+fn lower(schema: &[SchemaScope]) -> Vec<Module> {
+    schema
+        .iter()
+        .map(|scope| Module {
+            name: scope.schema_name.clone(),
+            classes: scope
+                .classes
+                .iter()
+                .map(|class| Class {
+                    name: class.class_name.clone(),
+                    members: class
+                        .offsets
+                        .iter()
+                        .map(|member| Member {
+                            name: member.field_name.clone(),
+                            offset: member.offset,
+                        })
+                        .collect(),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Reserved words in C++ and Rust that must not be emitted as bare
+/// identifiers. Members whose sanitized name collides with one of these are
+/// prefixed with an underscore.
+const RESERVED: &[&str] = &[
+    "alignas", "alignof", "and", "as", "asm", "auto", "bool", "break", "case", "catch", "char",
+    "class", "const", "constexpr", "continue", "crate", "decltype", "default", "delete", "do",
+    "double", "dyn", "else", "enum", "explicit", "extern", "false", "float", "fn", "for",
+    "friend", "goto", "if", "impl", "in", "inline", "int", "let", "long", "loop", "match", "mod",
+    "move", "mutable", "namespace", "new", "operator", "private", "protected", "pub", "public",
+    "ref", "register", "return", "self", "short", "signed", "sizeof", "static", "struct", "super",
+    "switch", "template", "this", "throw", "trait", "true", "try", "type", "typedef", "typeof",
+    "union", "unsafe", "unsigned", "use", "virtual", "void", "volatile", "where", "while",
+];
+
+/// Sanitize a schema or member name into a valid C++/Rust identifier by
+/// replacing everything that isn't alphanumeric with an underscore (e.g.
+/// `client.dll` becomes `client_dll`), prefixing an underscore when the result
+/// would start with a digit or collide with a reserved word.
+fn ident(name: &str) -> String {
+    let mut sanitized: String = name
+        .chars()
+        .map(|char| if char.is_ascii_alphanumeric() { char } else { '_' })
+        .collect();
+
+    if sanitized.is_empty() {
+        sanitized.push('_');
+    }
+
+    if sanitized.chars().next().is_some_and(|c| c.is_ascii_digit())
+        || RESERVED.contains(&sanitized.as_str())
+    {
+        sanitized.insert(0, '_');
+    }
+
+    sanitized
+}
+
+/// Ensure `candidate` is unique within `seen`, appending `_N` until it is. Keeps
+/// two members that sanitize to the same identifier from producing duplicate
+/// declarations.
+fn unique(candidate: String, seen: &mut HashSet<String>) -> String {
+    if seen.insert(candidate.clone()) {
+        return candidate;
+    }
+
+    let mut suffix = 1;
+    loop {
+        let alternative = format!("{}_{}", candidate, suffix);
+        if seen.insert(alternative.clone()) {
+            return alternative;
+        }
+        suffix += 1;
+    }
+}
+
+/// Generate a C++ header from the resolved schema. Each schema module becomes a
+/// `namespace`, each class a `struct` and each member an inline `constexpr`
+/// offset.
+pub fn generate_cpp(schema: &[SchemaScope]) -> String {
+    render_cpp(&lower(schema))
+}
+
+fn render_cpp(modules: &[Module]) -> String {
+    let mut output = String::new();
+    output.push_str("// Auto-generated by Valthrun DumpSchema. Do not edit.\n");
+    output.push_str("#pragma once\n\n#include <cstdint>\n");
+
+    let mut seen_modules = HashSet::new();
+    for module in modules {
+        let module_name = unique(ident(&module.name), &mut seen_modules);
+        write!(output, "\nnamespace {} {{\n", module_name).unwrap();
+        let mut seen_classes = HashSet::new();
+        for class in module.classes.iter() {
+            let mut seen = HashSet::new();
+            let class_name = unique(ident(&class.name), &mut seen_classes);
+            write!(output, "    struct {} {{\n", class_name).unwrap();
+            for member in class.members.iter() {
+                writeln!(
+                    output,
+                    "        static constexpr ::std::uint64_t {} = {:#x};",
+                    unique(ident(&member.name), &mut seen),
+                    member.offset,
+                )
+                .unwrap();
+            }
+            output.push_str("    };\n");
+        }
+        output.push_str("}\n");
+    }
+
+    output
+}
+
+/// Generate a Rust module tree of offset constants from the resolved schema.
+/// Each schema becomes a module, each class a submodule and each member a
+/// `const` offset.
+pub fn generate_rust(schema: &[SchemaScope]) -> String {
+    render_rust(&lower(schema))
+}
+
+fn render_rust(modules: &[Module]) -> String {
+    let mut output = String::new();
+    output.push_str("// Auto-generated by Valthrun DumpSchema. Do not edit.\n");
+    output.push_str("#![allow(non_snake_case, non_upper_case_globals)]\n");
+
+    let mut seen_modules = HashSet::new();
+    for module in modules {
+        let module_name = unique(ident(&module.name), &mut seen_modules);
+        write!(output, "\npub mod {} {{\n", module_name).unwrap();
+        let mut seen_classes = HashSet::new();
+        for class in module.classes.iter() {
+            let mut seen = HashSet::new();
+            let class_name = unique(ident(&class.name), &mut seen_classes);
+            write!(output, "    pub mod {} {{\n", class_name).unwrap();
+            for member in class.members.iter() {
+                writeln!(
+                    output,
+                    "        pub const {}: u64 = {:#x};",
+                    unique(ident(&member.name), &mut seen),
+                    member.offset,
+                )
+                .unwrap();
+            }
+            output.push_str("    }\n");
+        }
+        output.push_str("}\n");
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ident_sanitizes_keywords_and_leading_digits() {
+        assert_eq!(ident("client.dll"), "client_dll");
+        assert_eq!(ident("type"), "_type");
+        assert_eq!(ident("move"), "_move");
+        assert_eq!(ident("2d"), "_2d");
+        assert_eq!(ident(""), "_");
+    }
+
+    #[test]
+    fn unique_deduplicates_collisions() {
+        let mut seen = HashSet::new();
+        assert_eq!(unique("m_foo".to_string(), &mut seen), "m_foo");
+        assert_eq!(unique("m_foo".to_string(), &mut seen), "m_foo_1");
+        assert_eq!(unique("m_foo".to_string(), &mut seen), "m_foo_2");
+    }
+
+    /// A fixed input schema exercising a keyword member (`type`) and a sanitize
+    /// collision (`m.bar` and `m_bar`).
+    fn fixture() -> Vec<Module> {
+        vec![Module {
+            name: "client.dll".to_string(),
+            classes: vec![Class {
+                name: "C_BaseEntity".to_string(),
+                members: vec![
+                    Member {
+                        name: "type".to_string(),
+                        offset: 0x10,
+                    },
+                    Member {
+                        name: "m.bar".to_string(),
+                        offset: 0x20,
+                    },
+                    Member {
+                        name: "m_bar".to_string(),
+                        offset: 0x28,
+                    },
+                ],
+            }],
+        }]
+    }
+
+    #[test]
+    fn render_cpp_is_stable() {
+        let expected = "\
+// Auto-generated by Valthrun DumpSchema. Do not edit.
+#pragma once
+
+#include <cstdint>
+
+namespace client_dll {
+    struct C_BaseEntity {
+        static constexpr ::std::uint64_t _type = 0x10;
+        static constexpr ::std::uint64_t m_bar = 0x20;
+        static constexpr ::std::uint64_t m_bar_1 = 0x28;
+    };
+}
+";
+        assert_eq!(render_cpp(&fixture()), expected);
+    }
+
+    #[test]
+    fn render_deduplicates_class_and_module_names() {
+        /* Two modules and two classes that sanitize to the same identifier. */
+        let modules = vec![
+            Module {
+                name: "client.dll".to_string(),
+                classes: vec![
+                    Class {
+                        name: "C.Foo".to_string(),
+                        members: Vec::new(),
+                    },
+                    Class {
+                        name: "C_Foo".to_string(),
+                        members: Vec::new(),
+                    },
+                ],
+            },
+            Module {
+                name: "client_dll".to_string(),
+                classes: Vec::new(),
+            },
+        ];
+
+        let cpp = render_cpp(&modules);
+        assert!(cpp.contains("namespace client_dll {"));
+        assert!(cpp.contains("namespace client_dll_1 {"));
+        assert!(cpp.contains("struct C_Foo {"));
+        assert!(cpp.contains("struct C_Foo_1 {"));
+
+        let rust = render_rust(&modules);
+        assert!(rust.contains("pub mod client_dll {"));
+        assert!(rust.contains("pub mod client_dll_1 {"));
+        assert!(rust.contains("pub mod C_Foo {"));
+        assert!(rust.contains("pub mod C_Foo_1 {"));
+    }
+
+    #[test]
+    fn render_rust_is_stable() {
+        let expected = "\
+// Auto-generated by Valthrun DumpSchema. Do not edit.
+#![allow(non_snake_case, non_upper_case_globals)]
+
+pub mod client_dll {
+    pub mod C_BaseEntity {
+        pub const _type: u64 = 0x10;
+        pub const m_bar: u64 = 0x20;
+        pub const m_bar_1: u64 = 0x28;
+    }
+}
+";
+        assert_eq!(render_rust(&fixture()), expected);
+    }
+}
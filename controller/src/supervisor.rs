@@ -0,0 +1,121 @@
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use crate::enhancements::Enhancement;
+
+/// Shortest pause applied after the first failure. Subsequent failures double
+/// the pause up to [`MAX_BACKOFF`].
+const BASE_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Upper bound for the exponential backoff before an enhancement is quarantined.
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Number of consecutive failures after which an enhancement is quarantined.
+const QUARANTINE_THRESHOLD: u32 = 5;
+
+/// How often a quarantined enhancement is retried to see if it recovered.
+const QUARANTINE_RETRY: Duration = Duration::from_secs(30);
+
+/// Health of a single supervised enhancement, surfaced in the settings UI.
+#[derive(Debug, Clone)]
+pub enum HealthStatus {
+    /// Updating normally.
+    Healthy,
+    /// Temporarily skipped; will retry after `retry_in`.
+    BackingOff { retry_in: Duration },
+    /// Disabled after repeated failures; periodically retried.
+    Quarantined,
+}
+
+/// An [`Enhancement`] wrapped with independent health state so a failure in one
+/// hack only skips and backs off that hack, leaving the rest of the update loop
+/// running. Models a supervision tree child with its own restart policy.
+pub struct SupervisedEnhancement {
+    pub name: &'static str,
+    pub instance: Rc<RefCell<dyn Enhancement>>,
+
+    consecutive_failures: u32,
+    retry_at: Option<Instant>,
+    quarantined: bool,
+    last_error: Option<String>,
+}
+
+impl SupervisedEnhancement {
+    pub fn new(name: &'static str, instance: Rc<RefCell<dyn Enhancement>>) -> Self {
+        Self {
+            name,
+            instance,
+            consecutive_failures: 0,
+            retry_at: None,
+            quarantined: false,
+            last_error: None,
+        }
+    }
+
+    /// Whether this enhancement should be updated on the current frame. A
+    /// backing-off or quarantined enhancement is only ready again once its
+    /// retry timeout has elapsed.
+    pub fn ready(&self, now: Instant) -> bool {
+        self.retry_at.map_or(true, |retry_at| now >= retry_at)
+    }
+
+    /// Record a successful update, clearing any backoff and lifting quarantine.
+    pub fn record_success(&mut self) {
+        if self.quarantined {
+            log::info!("Enhancement {} recovered, lifting quarantine.", self.name);
+        }
+        self.consecutive_failures = 0;
+        self.retry_at = None;
+        self.quarantined = false;
+        self.last_error = None;
+    }
+
+    /// Record a failed update, extending the exponential backoff and
+    /// quarantining the enhancement once it keeps failing.
+    pub fn record_failure(&mut self, now: Instant, error: &anyhow::Error) {
+        self.consecutive_failures += 1;
+        self.last_error = Some(format!("{:#}", error));
+
+        if self.consecutive_failures >= QUARANTINE_THRESHOLD {
+            if !self.quarantined {
+                log::error!(
+                    "Quarantining enhancement {} after {} consecutive failures.",
+                    self.name,
+                    self.consecutive_failures
+                );
+            }
+            self.quarantined = true;
+            self.retry_at = Some(now + QUARANTINE_RETRY);
+        } else {
+            let backoff = (BASE_BACKOFF * (1 << (self.consecutive_failures - 1))).min(MAX_BACKOFF);
+            self.retry_at = Some(now + backoff);
+        }
+    }
+
+    /// Clear all health state, e.g. when the user manually resets the feature.
+    pub fn reset(&mut self) {
+        self.consecutive_failures = 0;
+        self.retry_at = None;
+        self.quarantined = false;
+        self.last_error = None;
+    }
+
+    pub fn status(&self, now: Instant) -> HealthStatus {
+        if self.quarantined {
+            HealthStatus::Quarantined
+        } else if let Some(retry_at) = self.retry_at.filter(|retry_at| *retry_at > now) {
+            HealthStatus::BackingOff {
+                retry_in: retry_at - now,
+            }
+        } else {
+            HealthStatus::Healthy
+        }
+    }
+
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+}
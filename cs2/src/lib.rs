@@ -12,6 +12,9 @@ pub use entity::*;
 mod offsets;
 pub use offsets::*;
 
+mod offset_db;
+pub use offset_db::*;
+
 pub mod offsets_manual;
 
 mod schema;
@@ -29,5 +32,8 @@ pub use globals::*;
 mod signature;
 pub use signature::*;
 
+mod read_profiler;
+pub use read_profiler::*;
+
 mod build;
 pub use build::*;
@@ -0,0 +1,175 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{BuildInfo, CS2Handle, CS2Offsets};
+
+/// How close the offset set selected for the running build is to the build it
+/// was captured on. Reported analogous to a protocol version handshake that
+/// advertises the feature level it still supports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompatibilityLevel {
+    /// A cached set captured on exactly this revision was found.
+    Exact,
+    /// No exact match, but a set from a revision known to be layout compatible
+    /// was selected.
+    Compatible,
+    /// No static set applied; offsets had to be re-derived at runtime.
+    Unknown,
+}
+
+/// A statically known offset set, keyed by the range of build revisions it is
+/// known to apply to. Ranges are matched inclusively. The offset payload itself
+/// lives in the persisted [`OffsetCache`] under `name`: the first time a build
+/// in the range is seen the derived set is stored there, and every later build
+/// in the range reuses it as a `Compatible` match.
+struct KnownBuild {
+    revision_min: u32,
+    revision_max: u32,
+    name: &'static str,
+}
+
+/// Registry of revision ranges that share an offset layout. When no range
+/// contains the running revision the nearest range is selected; the newest
+/// entries are listed first so they win when two ranges are equally near.
+const KNOWN_BUILDS: &[KnownBuild] = &[KnownBuild {
+    revision_min: 0,
+    revision_max: u32::MAX,
+    name: "baseline",
+}];
+
+/// CS2 patch revisions are dotted version strings (e.g. `1.40.1.2`). We compare
+/// builds by their trailing numeric component, which is monotonic across
+/// updates, so the range closest to it is the most layout-compatible fallback.
+fn revision_ordinal(revision: &str) -> Option<u32> {
+    revision.rsplit('.').next()?.parse().ok()
+}
+
+/// Distance from `ordinal` to a range, zero when it lies inside it.
+fn range_distance(ordinal: u32, build: &KnownBuild) -> u32 {
+    if ordinal < build.revision_min {
+        build.revision_min - ordinal
+    } else if ordinal > build.revision_max {
+        ordinal - build.revision_max
+    } else {
+        0
+    }
+}
+
+/// Select the known offset set whose revision range contains `revision`, or the
+/// range closest to it as a compatible fallback. Returns `None` when the
+/// revision can't be parsed and the offsets have to be re-derived at runtime.
+fn negotiate(revision: &str) -> Option<&'static KnownBuild> {
+    let ordinal = revision_ordinal(revision)?;
+    KNOWN_BUILDS
+        .iter()
+        .min_by_key(|build| range_distance(ordinal, build))
+}
+
+/// On-disk cache of resolved offset sets, keyed by build revision (and by the
+/// [`KnownBuild`] name for the set shared across its range) so a previously
+/// derived set is reused on the next launch instead of being resolved again.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct OffsetCache {
+    sets: BTreeMap<String, CS2Offsets>,
+}
+
+fn cache_path() -> PathBuf {
+    PathBuf::from("offsets.cache.json")
+}
+
+fn load_cache() -> OffsetCache {
+    let path = cache_path();
+    let file = match File::open(&path) {
+        Ok(file) => file,
+        Err(_) => return OffsetCache::default(),
+    };
+
+    match serde_json::from_reader(BufReader::new(file)) {
+        Ok(cache) => cache,
+        Err(error) => {
+            log::warn!("Ignoring corrupt offset cache: {}", error);
+            OffsetCache::default()
+        }
+    }
+}
+
+fn store_cache(cache: &OffsetCache) -> anyhow::Result<()> {
+    let file = File::options()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(cache_path())?;
+    serde_json::to_writer_pretty(BufWriter::new(file), cache)?;
+    Ok(())
+}
+
+impl CS2Offsets {
+    /// Resolve the offset set for the currently running `build`.
+    ///
+    /// The build revision is negotiated against the static [`KNOWN_BUILDS`]
+    /// registry and the persisted cache: an exact or compatible set is reused,
+    /// otherwise the offsets are re-derived at runtime via
+    /// [`CS2Offsets::resolve_offsets`] and persisted back to disk so the next
+    /// launch is fast. No schema walk through the controller's
+    /// `class_name_cache` is involved. Returns the resolved set together with the
+    /// [`CompatibilityLevel`] it was selected at.
+    pub fn resolve_for_build(
+        cs2: &Arc<CS2Handle>,
+        build: &BuildInfo,
+    ) -> anyhow::Result<(Self, CompatibilityLevel)> {
+        let mut cache = load_cache();
+
+        /* Exact: a set captured on precisely this revision is cached. */
+        if let Some(offsets) = cache.sets.get(&build.revision) {
+            log::info!(
+                "Offset compatibility for revision {}: {:?}",
+                build.revision,
+                CompatibilityLevel::Exact
+            );
+            return Ok((offsets.clone(), CompatibilityLevel::Exact));
+        }
+
+        /* Compatible: the negotiated known build's set is cached under its name. */
+        if let Some(known) = negotiate(&build.revision) {
+            if let Some(offsets) = cache.sets.get(known.name) {
+                log::info!(
+                    "Offset compatibility for revision {}: {:?} ({})",
+                    build.revision,
+                    CompatibilityLevel::Compatible,
+                    known.name
+                );
+                return Ok((offsets.clone(), CompatibilityLevel::Compatible));
+            }
+        }
+
+        /* Unknown: re-derive at runtime and persist for the next launch. */
+        log::info!(
+            "Offset compatibility for revision {}: {:?}, re-deriving offsets at runtime.",
+            build.revision,
+            CompatibilityLevel::Unknown
+        );
+        let offsets = Self::resolve_offsets(cs2)
+            .context("failed to re-derive offsets for unknown build")?;
+
+        cache.sets.insert(build.revision.clone(), offsets.clone());
+        if let Some(known) = negotiate(&build.revision) {
+            cache
+                .sets
+                .entry(known.name.to_string())
+                .or_insert_with(|| offsets.clone());
+        }
+        if let Err(error) = store_cache(&cache) {
+            log::warn!("Failed to persist resolved offsets: {}", error);
+        }
+
+        Ok((offsets, CompatibilityLevel::Unknown))
+    }
+}
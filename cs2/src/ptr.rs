@@ -1,6 +1,6 @@
 use cs2_schema_declaration::PtrCStr;
 
-use crate::CS2Handle;
+use crate::{record_bytes, CS2Handle};
 
 pub trait PCStrEx {
     fn read_string(&self, cs2: &CS2Handle) -> anyhow::Result<String>;
@@ -9,7 +9,9 @@ pub trait PCStrEx {
 
 impl PCStrEx for PtrCStr {
     fn read_string(&self, cs2: &CS2Handle) -> anyhow::Result<String> {
-        cs2.read_string(&[self.address()?], None)
+        let value = cs2.read_string(&[self.address()?], None)?;
+        record_bytes(value.len());
+        Ok(value)
     }
 
     fn try_read_string(&self, cs2: &CS2Handle) -> anyhow::Result<Option<String>> {
@@ -17,7 +19,9 @@ impl PCStrEx for PtrCStr {
         if address == 0 {
             Ok(None)
         } else {
-            Ok(Some(cs2.read_string(&[address], None)?))
+            let value = cs2.read_string(&[address], None)?;
+            record_bytes(value.len());
+            Ok(Some(value))
         }
     }
 }
@@ -0,0 +1,182 @@
+use std::cell::RefCell;
+
+/// Name of the implicit root span every frame is collected under.
+const ROOT_SPAN: &str = "frame";
+
+/// A single node in the per-frame read attribution tree.
+///
+/// Each node corresponds to one span that was opened via [`push_span`] while
+/// driver reads were issued. `reads` and `bytes` only account for the reads
+/// recorded directly inside this span; the accumulated totals including child
+/// spans are obtained via [`ReadProfileNode::total_reads`] /
+/// [`ReadProfileNode::total_bytes`].
+#[derive(Debug, Clone)]
+pub struct ReadProfileNode {
+    pub name: String,
+    pub reads: usize,
+    pub bytes: usize,
+    pub children: Vec<ReadProfileNode>,
+}
+
+impl ReadProfileNode {
+    /// Read calls issued in this span and all of its descendants.
+    pub fn total_reads(&self) -> usize {
+        self.reads
+            + self
+                .children
+                .iter()
+                .map(ReadProfileNode::total_reads)
+                .sum::<usize>()
+    }
+
+    /// Bytes read in this span and all of its descendants.
+    pub fn total_bytes(&self) -> usize {
+        self.bytes
+            + self
+                .children
+                .iter()
+                .map(ReadProfileNode::total_bytes)
+                .sum::<usize>()
+    }
+}
+
+/// Flat arena the thread-local profiler accumulates into. Using indices instead
+/// of owned child nodes lets the stack of open spans point into the tree.
+/// Recording reads and bytes does not allocate; opening a span that has no node
+/// yet (including the first span of each frame, after the reset) does.
+struct Node {
+    name: String,
+    reads: usize,
+    bytes: usize,
+    children: Vec<usize>,
+}
+
+struct Profiler {
+    nodes: Vec<Node>,
+    /// Stack of currently open spans, `stack[0]` is always the root.
+    stack: Vec<usize>,
+}
+
+impl Profiler {
+    fn new() -> Self {
+        let root = Node {
+            name: ROOT_SPAN.to_string(),
+            reads: 0,
+            bytes: 0,
+            children: Vec::new(),
+        };
+
+        Self {
+            nodes: vec![root],
+            stack: vec![0],
+        }
+    }
+
+    fn current(&self) -> usize {
+        *self.stack.last().unwrap_or(&0)
+    }
+
+    fn push(&mut self, name: &str) {
+        let parent = self.current();
+        let existing = self.nodes[parent]
+            .children
+            .iter()
+            .copied()
+            .find(|child| self.nodes[*child].name == name);
+
+        let index = match existing {
+            Some(index) => index,
+            None => {
+                let index = self.nodes.len();
+                self.nodes.push(Node {
+                    name: name.to_string(),
+                    reads: 0,
+                    bytes: 0,
+                    children: Vec::new(),
+                });
+                self.nodes[parent].children.push(index);
+                index
+            }
+        };
+
+        self.stack.push(index);
+    }
+
+    fn pop(&mut self) {
+        /* Never pop the implicit root span. */
+        if self.stack.len() > 1 {
+            self.stack.pop();
+        }
+    }
+
+    fn record_reads(&mut self, count: usize) {
+        let current = self.current();
+        self.nodes[current].reads += count;
+    }
+
+    fn record_bytes(&mut self, bytes: usize) {
+        let current = self.current();
+        self.nodes[current].bytes += bytes;
+    }
+
+    fn build(&self, index: usize) -> ReadProfileNode {
+        let node = &self.nodes[index];
+        ReadProfileNode {
+            name: node.name.clone(),
+            reads: node.reads,
+            bytes: node.bytes,
+            children: node
+                .children
+                .iter()
+                .map(|child| self.build(*child))
+                .collect(),
+        }
+    }
+}
+
+thread_local! {
+    static PROFILER: RefCell<Profiler> = RefCell::new(Profiler::new());
+}
+
+fn with_local<R>(f: impl FnOnce(&mut Profiler) -> R) -> R {
+    PROFILER.with(|profiler| f(&mut profiler.borrow_mut()))
+}
+
+/// Open a new read span on the calling thread. Every read recorded via
+/// [`record_reads`]/[`record_bytes`] is attributed to this span until the
+/// matching [`pop_span`] call. Spans with the same name sharing a parent are
+/// merged so repeated operations accumulate into a single node.
+pub fn push_span(name: &str) {
+    with_local(|profiler| profiler.push(name));
+}
+
+/// Close the most recently opened span. The implicit root span is never closed.
+pub fn pop_span() {
+    with_local(|profiler| profiler.pop());
+}
+
+/// Attribute `count` driver read calls to the currently open span. The call
+/// count is sampled from `CS2Handle::ke_interface.total_read_calls()` around
+/// each span so reads issued through any path (enhancements, `EntitySystem`,
+/// the caches) are all accounted for.
+pub fn record_reads(count: usize) {
+    with_local(|profiler| profiler.record_reads(count));
+}
+
+/// Attribute `bytes` read to the currently open span, recorded by the
+/// `read_string`/`read_slice` wrappers that know their payload size.
+pub fn record_bytes(bytes: usize) {
+    with_local(|profiler| profiler.record_bytes(bytes));
+}
+
+/// Return the accumulated read attribution tree for the frame and reset the
+/// profiler for the next one. Any spans still open are discarded together with
+/// the arena.
+pub fn take_frame_profile() -> ReadProfileNode {
+    PROFILER.with(|profiler| {
+        let mut profiler = profiler.borrow_mut();
+        let tree = profiler.build(0);
+        *profiler = Profiler::new();
+        tree
+    })
+}